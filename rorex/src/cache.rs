@@ -0,0 +1,115 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The default freshness window for a "latest rate" cache entry before it's
+/// considered stale and re-fetched from a provider.
+pub const DEFAULT_LATEST_TTL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    rate: f64,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Disk-backed cache of provider quotes, keyed by provider/base/target/date.
+///
+/// Latest-rate entries expire after a TTL since rates move throughout the
+/// day; historical entries are tied to a specific past date and never
+/// change once published, so they're cached permanently.
+pub struct Cache {
+    entries: HashMap<String, CacheEntry>,
+    path: PathBuf,
+}
+
+fn cache_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("rorex")
+        .join("cache.json")
+}
+
+fn key(provider: &str, base: &str, target: &str, date: Option<NaiveDate>) -> String {
+    match date {
+        Some(date) => format!("{provider}:{base}:{target}:{date}"),
+        None => format!("{provider}:{base}:{target}:latest"),
+    }
+}
+
+impl Cache {
+    /// Loads the cache from disk, starting empty if none exists yet.
+    pub fn load() -> Self {
+        let path = cache_path();
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Cache { entries, path }
+    }
+
+    /// Returns the cached rate if present and still fresh. Historical
+    /// entries (`date` is `Some`) are always considered fresh; latest-rate
+    /// entries (`date` is `None`) must be younger than `ttl`.
+    pub fn get(
+        &self,
+        provider: &str,
+        base: &str,
+        target: &str,
+        date: Option<NaiveDate>,
+        ttl: Duration,
+    ) -> Option<f64> {
+        let entry = self.entries.get(&key(provider, base, target, date))?;
+        if date.is_none() {
+            let age = Utc::now()
+                .signed_duration_since(entry.fetched_at)
+                .to_std()
+                .unwrap_or(Duration::MAX);
+            if age > ttl {
+                return None;
+            }
+        }
+        Some(entry.rate)
+    }
+
+    /// Records a freshly fetched rate and persists the cache to disk.
+    pub fn put(&mut self, provider: &str, base: &str, target: &str, date: Option<NaiveDate>, rate: f64) {
+        self.entries.insert(
+            key(provider, base, target, date),
+            CacheEntry {
+                rate,
+                fetched_at: Utc::now(),
+            },
+        );
+        self.save();
+    }
+
+    /// Folds entries fetched elsewhere (e.g. on a background thread that
+    /// cloned this cache) back in, preferring `other`'s values on conflict,
+    /// and persists the result. Without this, work done on a cloned `Cache`
+    /// never reaches the in-memory cache the UI thread reads from.
+    pub fn merge(&mut self, other: Cache) {
+        self.entries.extend(other.entries);
+        self.save();
+    }
+
+    fn save(&self) {
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(&self.entries) {
+            let _ = fs::write(&self.path, json);
+        }
+    }
+}
+
+impl Clone for Cache {
+    fn clone(&self) -> Self {
+        Cache {
+            entries: self.entries.clone(),
+            path: self.path.clone(),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use chrono::{Datelike, NaiveDate};
+
+/// Open/high/low/close summary of a rate series over one calendar week.
+#[derive(Clone, Copy, Debug)]
+pub struct Candle {
+    pub week_start: NaiveDate,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+/// Buckets a daily `(date, rate)` series into weekly OHLC candles.
+///
+/// `series` is expected sorted by date, as produced by `fetch_historical_rates`.
+pub fn weekly_candles(series: &[(NaiveDate, f64)]) -> Vec<Candle> {
+    let mut candles: Vec<Candle> = Vec::new();
+    for &(date, rate) in series {
+        let week_start = date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+        match candles.last_mut() {
+            Some(candle) if candle.week_start == week_start => {
+                candle.high = candle.high.max(rate);
+                candle.low = candle.low.min(rate);
+                candle.close = rate;
+            }
+            _ => candles.push(Candle {
+                week_start,
+                open: rate,
+                high: rate,
+                low: rate,
+                close: rate,
+            }),
+        }
+    }
+    candles
+}
@@ -0,0 +1,25 @@
+use thiserror::Error;
+
+/// Errors that can occur while fetching a quote from a [`QuotesProvider`](crate::providers::QuotesProvider).
+#[derive(Debug, Error)]
+pub enum ForexError {
+    /// The underlying HTTP request failed (connection, timeout, TLS, ...).
+    #[error("network request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The provider's response body could not be parsed into the expected shape.
+    #[error("failed to parse provider response: {0}")]
+    Deserialize(String),
+
+    /// One of the requested currency codes isn't recognized by the provider.
+    #[error("unknown currency code: {symbol}")]
+    InvalidCurrency { symbol: String },
+
+    /// The provider responded successfully at the HTTP level but reported its own error.
+    #[error("provider error {code}: {message}")]
+    ProviderError { code: String, message: String },
+
+    /// The requested currency pair isn't present in an otherwise successful response.
+    #[error("currency pair not found in provider response")]
+    PairNotFound,
+}
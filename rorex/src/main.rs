@@ -1,118 +1,165 @@
+mod cache;
+mod candles;
+mod error;
+mod money;
+mod providers;
+mod streaming;
+
+use cache::Cache;
+use candles::Candle;
 use chrono::{NaiveDate, Utc};
 use eframe::egui;
 use egui::{CentralPanel, ComboBox};
-use egui_plot::{Line, Plot, PlotPoints};
-use reqwest::blocking::Client;
-use serde::Deserialize;
+use egui_plot::{BoxElem, BoxPlot, BoxSpread, Line, Plot, PlotPoints};
+use providers::{CurrencyLayerProvider, ExchangeRateApiProvider, FixerProvider, QuotesProvider};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
 use std::thread;
 
-#[derive(Deserialize)]
-struct ForexResponse {
-    conversion_rates: HashMap<String, f64>,
-}
+/// Selectable lookback windows for the historical rate chart.
+const HISTORICAL_RANGES_DAYS: [i64; 3] = [7, 30, 90];
+
+/// A freshly fetched rate, tagged with the provider and pair it came from so
+/// it can be written back into the cache once it reaches the main thread.
+type FetchedRate = (String, String, String, f64);
 
-#[derive(Deserialize)]
-struct HistoricalResponse {
-    rates: HashMap<String, HashMap<String, f64>>,
+/// A message sent back from a background fetch to the UI thread.
+pub(crate) enum FetchMessage {
+    /// A latest-rate fetch succeeded.
+    Rate(FetchedRate),
+    /// A fetch (latest or historical) or the live ticker stream failed;
+    /// carries a human-readable message.
+    Error(String),
+    /// The historical-rates fetch finished, carrying the daily series plus
+    /// the (possibly-updated) cache clone the fetch ran against, so newly
+    /// fetched entries can be folded back into the UI thread's cache.
+    Historical(Vec<(NaiveDate, f64)>, Cache),
+    /// A watchlist refresh finished; carries the rate (or error) for each
+    /// pair plus the (possibly-updated) cache clone the fetch ran against, so
+    /// newly fetched entries can be folded back into the UI thread's cache.
+    WatchlistRates(HashMap<(String, String), Result<f64, error::ForexError>>, Cache),
+    /// A tick from the live ticker WebSocket feed, tagged with the pair it's
+    /// for so a stale update from an earlier stream can be told apart from
+    /// one for the pair currently selected.
+    Live(String, f64),
 }
 
-/// Fetches the forex rate for a given currency pair using the provided API key.
-///
-/// # Arguments
-///
-/// * `api_key` - The API key for the ExchangeRate-API service.
-/// * `pair` - The currency pair to fetch the rate for. Format: "base_currencytarget_currency".
-///
-/// # Returns
-///
-/// * `Result<f64, String>` - The forex rate if successful, otherwise an error message.
-///
-/// # Errors
-///
-/// If the HTTP request fails or the JSON deserialization fails, an error message is returned.
-/// If the currency pair is not found in the response, an error message is returned.
-pub fn fetch_forex_rate(api_key: &str, pair: &str) -> Result<f64, String> {
-    // Construct the URL for the API request
-    let url = format!("https://v6.exchangerate-api.com/v6/{}/latest/{}", api_key, &pair[..3]);
-
-    // Create a new HTTP client
-    let client = Client::new();
-
-    // Send a GET request to the API and get the response
-    let response = client.get(&url)
-        .send()
-        .map_err(|e| e.to_string())?;
-
-    // Deserialize the API response into a struct
-    let forex_response: ForexResponse = response
-        .json()
-        .map_err(|e| e.to_string())?;
-
-    // Get the target currency rate from the response
-    let target_currency = &pair[3..];
-    forex_response.conversion_rates.get(target_currency)
-        .copied()
-        .ok_or_else(|| {
-            "Currency pair not found".to_string()
-        })
+/// Builds the list of quote providers to try, in fallback order: the primary
+/// ExchangeRate-API key first, then CurrencyLayer, then Fixer.
+fn build_providers(
+    exchangerate_api_key: &str,
+    currencylayer_api_key: &str,
+    fixer_api_key: &str,
+) -> Vec<Box<dyn QuotesProvider>> {
+    vec![
+        Box::new(ExchangeRateApiProvider {
+            api_key: exchangerate_api_key.to_string(),
+        }),
+        Box::new(CurrencyLayerProvider {
+            api_key: currencylayer_api_key.to_string(),
+        }),
+        Box::new(FixerProvider {
+            api_key: fixer_api_key.to_string(),
+        }),
+    ]
 }
 
-fn fetch_historical_rates(api_key: &str, pair: &str) -> Result<Vec<(NaiveDate, f64)>, String> {
-    let base_currency: &str = &pair[..3];
-    let target_currency: &str = &pair[3..];
+fn fetch_historical_rates(
+    providers: &[Box<dyn QuotesProvider>],
+    cache: &mut Cache,
+    base_currency: &str,
+    target_currency: &str,
+    range_days: i64,
+) -> Result<Vec<(NaiveDate, f64)>, error::ForexError> {
     let mut rates: Vec<(NaiveDate, f64)> = Vec::new();
     let end_date: NaiveDate = Utc::now().date_naive();
-    let start_date: NaiveDate = end_date - chrono::Duration::days(30);
-
-    for date in (0..=30).map(|i| start_date + chrono::Duration::days(i)) {
-        let url = format!(
-            "https://v6.exchangerate-api.com/v6/{}/history/{}/{}?start_date={}&end_date={}",
-            api_key, base_currency, target_currency, start_date, end_date
-        );
-
-        let client = Client::new();
-        let response = client.get(&url).send().map_err(|e| e.to_string())?;
-        let historical_response: HistoricalResponse = response.json().map_err(|e| e.to_string())?;
+    let start_date: NaiveDate = end_date - chrono::Duration::days(range_days);
 
-        if let Some(rate) = historical_response.rates.get(&date.to_string()) {
-            if let Some(&rate) = rate.get(target_currency) {
-                rates.push((date, rate));
+    for date in (0..=range_days).map(|i| start_date + chrono::Duration::days(i)) {
+        // Historical points never change once published, so a cache hit for
+        // any provider is as good as a fresh fetch.
+        let cached = providers
+            .iter()
+            .find_map(|p| cache.get(p.name(), base_currency, target_currency, Some(date), cache::DEFAULT_LATEST_TTL));
+        let rate = match cached {
+            Some(rate) => rate,
+            None => {
+                let (provider, rate) =
+                    providers::fetch_historical_rate(providers, base_currency, target_currency, date)?;
+                cache.put(provider, base_currency, target_currency, Some(date), rate);
+                rate
             }
-        }
+        };
+        rates.push((date, rate));
     }
 
     Ok(rates)
 }
 
+/// Renders a weekly OHLC candle as a box-plot element: the open/close form
+/// the box (quartiles) and the week's low/high form the whiskers.
+fn candle_to_box_elem(x: f64, candle: &Candle) -> BoxElem {
+    let (q1, q3) = if candle.open <= candle.close {
+        (candle.open, candle.close)
+    } else {
+        (candle.close, candle.open)
+    };
+    let median = (candle.open + candle.close) / 2.0;
+    BoxElem::new(x, BoxSpread::new(candle.low, q1, median, q3, candle.high))
+}
+
 /// Represents the application state.
 struct App {
     /// The API key for the ExchangeRate-API service.
     api_key: String,
+    /// The API key for the CurrencyLayer fallback provider.
+    currencylayer_api_key: String,
+    /// The API key for the Fixer fallback provider.
+    fixer_api_key: String,
     /// The base currency for forex conversion.
     base_currency: String,
     /// The target currency for forex conversion.
     target_currency: String,
     /// The forex rate if it has been fetched.
     rate: Option<f64>,
+    /// The amount, in the base currency, to convert using `rate`.
+    amount_input: String,
     /// The sender end of a channel for fetching the forex rate.
-    fetch_rate_tx: Sender<Option<f64>>,
+    fetch_rate_tx: Sender<FetchMessage>,
     /// The receiver end of a channel for fetching the forex rate.
-    fetch_rate_rx: Receiver<Option<f64>>,
+    fetch_rate_rx: Receiver<FetchMessage>,
+    /// The most recent fetch error, if any, shown in the panel until a
+    /// latest rate is next obtained (fetched, read from cache, or ticked live).
+    last_error: Option<String>,
     /// The list of available currencies.
     currencies: Vec<&'static str>,
-    /// The trend of historical rates.
-    ///
-    /// The trend is represented as a vector of `f64` values, where each value
-    /// represents the rate on a specific date. The dates are not explicitly
-    /// stored in the vector, but can be inferred from the vector index.
-    trend: Vec<f64>,
     /// The historical rates for a given currency pair.
     ///
     /// The historical rates are represented as a vector of tuples, where each
     /// tuple contains the date and the rate on that date.
     historical_rates: Vec<(NaiveDate, f64)>,
+    /// The lookback window, in days, used for the historical rate chart.
+    historical_range_days: i64,
+    /// Disk-backed cache of previously fetched rates, consulted before
+    /// hitting the network so repeated fetches don't burn API quota.
+    cache: Cache,
+    /// Currency pairs the user has added to the watchlist.
+    watchlist: Vec<(String, String)>,
+    /// Most recently fetched rate (or error) for each watchlist pair.
+    watchlist_rates: HashMap<(String, String), Result<f64, String>>,
+    /// Whether the live ticker WebSocket stream is currently running.
+    live_stream_running: bool,
+    /// Stop flag for the currently running streaming thread, if any. A fresh
+    /// one is created each time streaming starts (rather than reused across
+    /// restarts) so a lingering thread from a previous pair can't be mistaken
+    /// for the current one.
+    live_stream_active: Option<Arc<AtomicBool>>,
+    /// The pair the running stream is subscribed to, if any. Compared against
+    /// `base_currency`/`target_currency` each frame so a pair change while
+    /// streaming is already on triggers a restart instead of silently going stale.
+    live_stream_pair: Option<String>,
 }
 /// Represents the application state.
 impl App {
@@ -126,12 +173,18 @@ impl App {
         App {
             // API key for the ExchangeRate-API service
             api_key: "".to_string(),
+            // API key for the CurrencyLayer fallback provider
+            currencylayer_api_key: "".to_string(),
+            // API key for the Fixer fallback provider
+            fixer_api_key: "".to_string(),
             // Base currency for forex conversion
             base_currency: "USD".to_string(),
             // Target currency for forex conversion
             target_currency: "EUR".to_string(),
             // Fetched forex rate
             rate: None,
+            // Default amount to convert
+            amount_input: "1".to_string(),
             // Sender end of a channel for fetching the forex rate
             fetch_rate_tx,
             // Receiver end of a channel for fetching the forex rate
@@ -153,15 +206,43 @@ impl App {
                 "UAH", "UGX", "UYU", "UZS", "VES", "VND", "VUV", "WST", "XAF", "XCD", "XDR", "XOF", "XPF",
                 "YER", "ZAR", "ZMW", "ZWL",
             ],
-            // Trend of historical rates
-            // The trend is represented as a vector of `f64` values, where each value
-            // represents the rate on a specific date. The dates are not explicitly
-            // stored in the vector, but can be inferred from the vector index.
-            trend: Vec::new(),
             // Historical rates for a given currency pair
             // The historical rates are represented as a vector of tuples, where each
             // tuple contains the date and the rate on that date.
             historical_rates: Vec::new(),
+            // Default lookback window for the historical chart
+            historical_range_days: 30,
+            // Disk-backed cache of previously fetched rates
+            cache: Cache::load(),
+            // No fetch has failed yet
+            last_error: None,
+            // Watchlist starts empty
+            watchlist: Vec::new(),
+            watchlist_rates: HashMap::new(),
+            // Live streaming starts disabled
+            live_stream_running: false,
+            live_stream_active: None,
+            live_stream_pair: None,
+        }
+    }
+
+    /// Stops any currently running stream and spawns a new one for `pair`.
+    fn start_live_stream(&mut self, pair: String) {
+        self.stop_live_stream();
+        self.live_stream_running = true;
+        let active = Arc::new(AtomicBool::new(true));
+        self.live_stream_active = Some(active.clone());
+        self.live_stream_pair = Some(pair.clone());
+        let tx = self.fetch_rate_tx.clone();
+        thread::spawn(move || streaming::stream_ticker(pair, tx, active));
+    }
+
+    /// Signals the running stream's thread to stop, if one is running.
+    fn stop_live_stream(&mut self) {
+        self.live_stream_running = false;
+        self.live_stream_pair = None;
+        if let Some(active) = self.live_stream_active.take() {
+            active.store(false, Ordering::Relaxed);
         }
     }
 }
@@ -172,10 +253,20 @@ impl eframe::App for App {
             ui.heading("Forex Rate Fetcher");
 
             ui.horizontal(|ui| {
-                ui.label("API Key:");
+                ui.label("ExchangeRate-API Key:");
                 ui.text_edit_singleline(&mut self.api_key);
             });
 
+            ui.horizontal(|ui| {
+                ui.label("CurrencyLayer Key (fallback):");
+                ui.text_edit_singleline(&mut self.currencylayer_api_key);
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Fixer Key (fallback):");
+                ui.text_edit_singleline(&mut self.fixer_api_key);
+            });
+
             ui.horizontal(|ui| {
                 ui.label("Base Currency:");
                 ComboBox::from_id_source("base_currency")
@@ -197,40 +288,84 @@ impl eframe::App for App {
             });
 
             if ui.button("Fetch Rate").clicked() {
-                let pair = format!("{}{}", self.base_currency, self.target_currency);
-                let api_key = self.api_key.clone();
-                let tx = self.fetch_rate_tx.clone();
-                thread::spawn(move || {
-                    let rate = fetch_forex_rate(&api_key, &pair).ok();
-                    tx.send(rate).ok();
-                });
-            }
+                let base = self.base_currency.clone();
+                let target = self.target_currency.clone();
+                let providers = build_providers(&self.api_key, &self.currencylayer_api_key, &self.fixer_api_key);
 
-            if ui.button("Fetch Historical Rates").clicked() {
-                let pair = format!("{}{}", self.base_currency, self.target_currency);
-                let api_key = self.api_key.clone();
-                let tx = self.fetch_rate_tx.clone();
-                thread::spawn({
-                    let mut trend = self.trend.clone();
-                    move || {
-                        let rates = fetch_historical_rates(&api_key, &pair).ok();
-                        if let Some(rates) = rates {
-                            for (_, rate) in rates {
-                                trend.push(rate);
-                            }
-                        }
-                        tx.send(Some(0.0)).ok(); // Dummy send to trigger update
-                    }
-                });
+                let cached = providers
+                    .iter()
+                    .find_map(|p| self.cache.get(p.name(), &base, &target, None, cache::DEFAULT_LATEST_TTL));
+                if let Some(rate) = cached {
+                    self.last_error = None;
+                    self.rate = Some(rate);
+                } else {
+                    let tx = self.fetch_rate_tx.clone();
+                    thread::spawn(move || {
+                        let msg = match providers::fetch_forex_rate(&providers, &base, &target) {
+                            Ok((provider, rate)) => FetchMessage::Rate((provider.to_string(), base, target, rate)),
+                            Err(e) => FetchMessage::Error(e.to_string()),
+                        };
+                        tx.send(msg).ok();
+                    });
+                }
             }
 
+            ui.horizontal(|ui| {
+                ui.label("Range:");
+                for range in HISTORICAL_RANGES_DAYS {
+                    ui.selectable_value(&mut self.historical_range_days, range, format!("{range}d"));
+                }
+                if ui.button("Fetch Historical Rates").clicked() {
+                    let base = self.base_currency.clone();
+                    let target = self.target_currency.clone();
+                    let providers = build_providers(&self.api_key, &self.currencylayer_api_key, &self.fixer_api_key);
+                    let mut cache = self.cache.clone();
+                    let range_days = self.historical_range_days;
+                    let tx = self.fetch_rate_tx.clone();
+                    thread::spawn(move || {
+                        let msg = match fetch_historical_rates(&providers, &mut cache, &base, &target, range_days) {
+                            Ok(rates) => FetchMessage::Historical(rates, cache),
+                            Err(e) => FetchMessage::Error(e.to_string()),
+                        };
+                        tx.send(msg).ok();
+                    });
+                }
+            });
+
             match self.fetch_rate_rx.try_recv() {
-                Ok(rate) => {
-                    if let Some(rate) = rate {
+                Ok(FetchMessage::Rate((provider, base, target, rate))) => {
+                    self.cache.put(&provider, &base, &target, None, rate);
+                    self.rate = Some(rate);
+                    self.last_error = None;
+                }
+                Ok(FetchMessage::Error(message)) => {
+                    self.last_error = Some(message);
+                }
+                Ok(FetchMessage::WatchlistRates(rates, cache)) => {
+                    self.cache.merge(cache);
+                    for (pair, result) in rates {
+                        self.watchlist_rates.insert(pair, result.map_err(|e| e.to_string()));
+                    }
+                }
+                Ok(FetchMessage::Historical(rates, cache)) => {
+                    self.cache.merge(cache);
+                    self.historical_rates = rates;
+                }
+                Ok(FetchMessage::Live(pair, rate)) => {
+                    // A thread for a pair the user has since switched away
+                    // from (or stopped) may still have a message in flight;
+                    // ignore anything that isn't for the currently selected pair.
+                    let current_pair = format!("{}/{}", self.base_currency, self.target_currency);
+                    if pair == current_pair {
                         self.rate = Some(rate);
+                        self.last_error = None;
                     }
                 }
-                _ => (),
+                Err(_) => (),
+            }
+
+            if let Some(error) = &self.last_error {
+                ui.colored_label(egui::Color32::RED, error);
             }
 
             if let Some(rate) = self.rate {
@@ -239,13 +374,85 @@ impl eframe::App for App {
                 ui.label("Rate: Not fetched");
             }
 
-            if !self.trend.is_empty() {
-                let values: PlotPoints = self.trend.iter().enumerate().map(|(i, &y)| [i as f64, y]).collect();
-                let line = Line::new(values);
-                Plot::new("trend_plot").view_aspect(2.0).show(ui, |plot_ui| {
-                    plot_ui.line(line);
+            ui.horizontal(|ui| {
+                ui.label("Amount:");
+                ui.text_edit_singleline(&mut self.amount_input);
+            });
+
+            if let Some(rate) = self.rate {
+                if let Ok(amount) = self.amount_input.parse::<f64>() {
+                    match (
+                        money::format_amount(amount, &self.base_currency),
+                        money::format_amount(amount * rate, &self.target_currency),
+                    ) {
+                        (Ok(from), Ok(to)) => {
+                            ui.label(format!("{from} = {to}"));
+                        }
+                        (Err(e), _) | (_, Err(e)) => {
+                            ui.colored_label(egui::Color32::RED, e);
+                        }
+                    }
+                }
+            }
+
+            let mut live_stream_toggled = self.live_stream_running;
+            ui.checkbox(&mut live_stream_toggled, "Live streaming (WebSocket ticker)");
+            let current_pair = format!("{}/{}", self.base_currency, self.target_currency);
+            if live_stream_toggled && !self.live_stream_running {
+                self.start_live_stream(current_pair);
+            } else if !live_stream_toggled && self.live_stream_running {
+                self.stop_live_stream();
+            } else if self.live_stream_running && self.live_stream_pair.as_deref() != Some(current_pair.as_str()) {
+                // The user changed currencies while streaming was already on;
+                // restart against the new pair instead of leaving the old
+                // thread subscribed to a pair nothing reads anymore.
+                self.start_live_stream(current_pair);
+            }
+
+            ui.separator();
+            ui.heading("Watchlist");
+
+            ui.horizontal(|ui| {
+                let pair = (self.base_currency.clone(), self.target_currency.clone());
+                if ui.button("Add Current Pair").clicked() && !self.watchlist.contains(&pair) {
+                    self.watchlist.push(pair);
+                }
+                if ui.button("Refresh Watchlist").clicked() && !self.watchlist.is_empty() {
+                    let pairs = self.watchlist.clone();
+                    let providers = build_providers(&self.api_key, &self.currencylayer_api_key, &self.fixer_api_key);
+                    let tx = self.fetch_rate_tx.clone();
+                    let mut cache = self.cache.clone();
+                    thread::spawn(move || {
+                        let rates = providers::fetch_rates_batch(&providers, &mut cache, &pairs);
+                        tx.send(FetchMessage::WatchlistRates(rates, cache)).ok();
+                    });
+                }
+            });
+
+            let mut to_remove = None;
+            for (base, target) in &self.watchlist {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{base}/{target}"));
+                    match self.watchlist_rates.get(&(base.clone(), target.clone())) {
+                        Some(Ok(rate)) => {
+                            ui.label(format!("{rate}"));
+                        }
+                        Some(Err(message)) => {
+                            ui.colored_label(egui::Color32::RED, message);
+                        }
+                        None => {
+                            ui.label("(not fetched)");
+                        }
+                    }
+                    if ui.button("Remove").clicked() {
+                        to_remove = Some((base.clone(), target.clone()));
+                    }
                 });
             }
+            if let Some(pair) = to_remove {
+                self.watchlist.retain(|p| p != &pair);
+                self.watchlist_rates.remove(&pair);
+            }
 
             if !self.historical_rates.is_empty() {
                 let values: PlotPoints = self
@@ -258,6 +465,16 @@ impl eframe::App for App {
                 Plot::new("historical_plot").view_aspect(2.0).show(ui, |plot_ui| {
                     plot_ui.line(line);
                 });
+
+                let candles = candles::weekly_candles(&self.historical_rates);
+                let boxes: Vec<BoxElem> = candles
+                    .iter()
+                    .enumerate()
+                    .map(|(i, candle)| candle_to_box_elem(i as f64, candle))
+                    .collect();
+                Plot::new("historical_candles").view_aspect(2.0).show(ui, |plot_ui| {
+                    plot_ui.box_plot(BoxPlot::new(boxes));
+                });
             }
         });
     }
@@ -0,0 +1,31 @@
+use rust_decimal::Decimal;
+use rusty_money::{iso, Money, Round};
+
+/// Formats `amount` as a currency string using the correct number of decimal
+/// places, grouping, and symbol for the given ISO currency code (e.g. 0
+/// decimals for JPY, 3 for BHD).
+pub fn format_amount(amount: f64, currency_code: &str) -> Result<String, String> {
+    let currency = iso::find(currency_code)
+        .ok_or_else(|| format!("unknown ISO currency code: {currency_code}"))?;
+    // `from_f64` (not `from_f64_retain`) rounds to the nearest clean decimal
+    // instead of preserving the binary float's exact bit pattern.
+    let decimal = Decimal::from_f64(amount)
+        .ok_or_else(|| format!("amount {amount} can't be represented as a decimal"))?;
+    let money = Money::from_decimal(decimal, currency).round(currency.exponent as i32, Round::HalfEven);
+    Ok(money.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jpy_has_zero_decimal_places() {
+        assert_eq!(format_amount(1234.5, "JPY").unwrap(), "¥1,234");
+    }
+
+    #[test]
+    fn bhd_has_three_decimal_places() {
+        assert_eq!(format_amount(1.2345, "BHD").unwrap(), "د.ب1.234");
+    }
+}
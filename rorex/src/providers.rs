@@ -0,0 +1,467 @@
+use crate::cache::{self, Cache};
+use crate::error::ForexError;
+use chrono::NaiveDate;
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// A source of forex quotes.
+///
+/// `App` holds a list of providers and tries them in order, so a single API
+/// going down or hitting its quota doesn't stop the user from getting a rate.
+///
+/// `Send + Sync` because providers are built on the UI thread and then moved
+/// into background fetch threads via `Vec<Box<dyn QuotesProvider>>`.
+pub trait QuotesProvider: Send + Sync {
+    /// A short, human-readable name for this provider, used to key its cache
+    /// entries and to report which provider served a quote.
+    fn name(&self) -> &'static str;
+
+    /// The latest spot rate for converting one unit of `base` into `target`.
+    fn latest(&self, base: &str, target: &str) -> Result<f64, ForexError>;
+
+    /// The rate for converting `base` into `target` on a specific past `date`.
+    fn historical(&self, base: &str, target: &str, date: NaiveDate) -> Result<f64, ForexError>;
+
+    /// Latest rates for `base` against every currency in `targets`, ideally in
+    /// a single request. The default implementation just loops over `latest`;
+    /// providers whose API returns every rate (or accepts a target list) in
+    /// one call should override this to avoid the extra round trips.
+    fn latest_batch(&self, base: &str, targets: &[String]) -> Result<HashMap<String, f64>, ForexError> {
+        targets
+            .iter()
+            .map(|target| self.latest(base, target).map(|rate| (target.clone(), rate)))
+            .collect()
+    }
+}
+
+fn rate_or_pair_not_found(rates: &HashMap<String, f64>, target: &str) -> Result<f64, ForexError> {
+    rates.get(target).copied().ok_or(ForexError::PairNotFound)
+}
+
+/// Turns an ExchangeRate-API `error-type` code into an actionable message.
+/// See <https://www.exchangerate-api.com/docs/standard-requests> for the full list.
+fn exchangerate_api_message(error_type: &str) -> String {
+    match error_type {
+        "unsupported-code" => "that currency code isn't supported by ExchangeRate-API".to_string(),
+        "malformed-request" => "malformed request sent to ExchangeRate-API".to_string(),
+        "invalid-key" => "invalid ExchangeRate-API key".to_string(),
+        "inactive-account" => "ExchangeRate-API account is inactive (confirm your email)".to_string(),
+        "quota-reached" => "ExchangeRate-API quota reached for this billing period".to_string(),
+        other => format!("ExchangeRate-API error: {other}"),
+    }
+}
+
+/// <https://www.exchangerate-api.com/>
+pub struct ExchangeRateApiProvider {
+    pub api_key: String,
+}
+
+#[derive(Deserialize)]
+struct ExchangeRateApiLatest {
+    result: String,
+    #[serde(rename = "error-type")]
+    error_type: Option<String>,
+    conversion_rates: Option<HashMap<String, f64>>,
+}
+
+impl ExchangeRateApiLatest {
+    fn into_rate(self, target: &str) -> Result<f64, ForexError> {
+        if self.result != "success" {
+            let error_type = self.error_type.unwrap_or_else(|| "unknown-error".to_string());
+            if error_type == "unsupported-code" {
+                return Err(ForexError::InvalidCurrency { symbol: target.to_string() });
+            }
+            return Err(ForexError::ProviderError {
+                code: error_type.clone(),
+                message: exchangerate_api_message(&error_type),
+            });
+        }
+        rate_or_pair_not_found(&self.conversion_rates.unwrap_or_default(), target)
+    }
+}
+
+impl QuotesProvider for ExchangeRateApiProvider {
+    fn name(&self) -> &'static str {
+        "ExchangeRate-API"
+    }
+
+    fn latest(&self, base: &str, target: &str) -> Result<f64, ForexError> {
+        let url = format!(
+            "https://v6.exchangerate-api.com/v6/{}/latest/{base}",
+            self.api_key
+        );
+        let body: ExchangeRateApiLatest = Client::new()
+            .get(&url)
+            .send()?
+            .json()
+            .map_err(|e| ForexError::Deserialize(e.to_string()))?;
+        body.into_rate(target)
+    }
+
+    fn latest_batch(&self, base: &str, targets: &[String]) -> Result<HashMap<String, f64>, ForexError> {
+        // One `/latest/{base}` call already returns every conversion rate, so
+        // a whole watchlist group sharing this base costs a single request.
+        let url = format!(
+            "https://v6.exchangerate-api.com/v6/{}/latest/{base}",
+            self.api_key
+        );
+        let body: ExchangeRateApiLatest = Client::new()
+            .get(&url)
+            .send()?
+            .json()
+            .map_err(|e| ForexError::Deserialize(e.to_string()))?;
+
+        if body.result != "success" {
+            let error_type = body.error_type.unwrap_or_else(|| "unknown-error".to_string());
+            return Err(ForexError::ProviderError {
+                code: error_type.clone(),
+                message: exchangerate_api_message(&error_type),
+            });
+        }
+        let rates = body.conversion_rates.unwrap_or_default();
+        targets
+            .iter()
+            .map(|target| {
+                rates
+                    .get(target)
+                    .copied()
+                    .map(|rate| (target.clone(), rate))
+                    .ok_or_else(|| ForexError::InvalidCurrency { symbol: target.clone() })
+            })
+            .collect()
+    }
+
+    fn historical(&self, base: &str, target: &str, date: NaiveDate) -> Result<f64, ForexError> {
+        let url = format!(
+            "https://v6.exchangerate-api.com/v6/{}/history/{base}/{}/{}/{}",
+            self.api_key,
+            date.format("%Y"),
+            date.format("%-m"),
+            date.format("%-d"),
+        );
+        let body: ExchangeRateApiLatest = Client::new()
+            .get(&url)
+            .send()?
+            .json()
+            .map_err(|e| ForexError::Deserialize(e.to_string()))?;
+        body.into_rate(target)
+    }
+}
+
+/// <https://currencylayer.com/>
+pub struct CurrencyLayerProvider {
+    pub api_key: String,
+}
+
+#[derive(Deserialize)]
+struct CurrencyLayerError {
+    code: i32,
+    info: String,
+}
+
+#[derive(Deserialize)]
+struct CurrencyLayerResponse {
+    success: bool,
+    error: Option<CurrencyLayerError>,
+    quotes: Option<HashMap<String, f64>>,
+}
+
+impl CurrencyLayerResponse {
+    fn into_rate(self, base: &str, target: &str) -> Result<f64, ForexError> {
+        if !self.success {
+            let error = self.error.unwrap_or(CurrencyLayerError {
+                code: 0,
+                info: "unknown error".to_string(),
+            });
+            // 201/202: invalid source/target currency codes.
+            if error.code == 201 || error.code == 202 {
+                return Err(ForexError::InvalidCurrency { symbol: format!("{base}/{target}") });
+            }
+            return Err(ForexError::ProviderError {
+                code: error.code.to_string(),
+                message: format!("CurrencyLayer error: {}", error.info),
+            });
+        }
+        let quotes = self.quotes.unwrap_or_default();
+        rate_or_pair_not_found(&quotes, &format!("{base}{target}"))
+    }
+}
+
+impl QuotesProvider for CurrencyLayerProvider {
+    fn name(&self) -> &'static str {
+        "CurrencyLayer"
+    }
+
+    fn latest(&self, base: &str, target: &str) -> Result<f64, ForexError> {
+        let url = format!(
+            "https://apilayer.net/api/live?access_key={}&source={base}&currencies={target}",
+            self.api_key
+        );
+        let body: CurrencyLayerResponse = Client::new()
+            .get(&url)
+            .send()?
+            .json()
+            .map_err(|e| ForexError::Deserialize(e.to_string()))?;
+        body.into_rate(base, target)
+    }
+
+    fn latest_batch(&self, base: &str, targets: &[String]) -> Result<HashMap<String, f64>, ForexError> {
+        let url = format!(
+            "https://apilayer.net/api/live?access_key={}&source={base}&currencies={}",
+            self.api_key,
+            targets.join(",")
+        );
+        let body: CurrencyLayerResponse = Client::new()
+            .get(&url)
+            .send()?
+            .json()
+            .map_err(|e| ForexError::Deserialize(e.to_string()))?;
+        if !body.success {
+            let error = body.error.unwrap_or(CurrencyLayerError {
+                code: 0,
+                info: "unknown error".to_string(),
+            });
+            return Err(ForexError::ProviderError {
+                code: error.code.to_string(),
+                message: format!("CurrencyLayer error: {}", error.info),
+            });
+        }
+        let quotes = body.quotes.unwrap_or_default();
+        targets
+            .iter()
+            .map(|target| {
+                quotes
+                    .get(&format!("{base}{target}"))
+                    .copied()
+                    .map(|rate| (target.clone(), rate))
+                    .ok_or_else(|| ForexError::InvalidCurrency { symbol: target.clone() })
+            })
+            .collect()
+    }
+
+    fn historical(&self, base: &str, target: &str, date: NaiveDate) -> Result<f64, ForexError> {
+        let url = format!(
+            "https://apilayer.net/api/historical?access_key={}&date={date}&source={base}&currencies={target}",
+            self.api_key
+        );
+        let body: CurrencyLayerResponse = Client::new()
+            .get(&url)
+            .send()?
+            .json()
+            .map_err(|e| ForexError::Deserialize(e.to_string()))?;
+        body.into_rate(base, target)
+    }
+}
+
+/// <https://fixer.io/>
+pub struct FixerProvider {
+    pub api_key: String,
+}
+
+#[derive(Deserialize)]
+struct FixerError {
+    code: i32,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Deserialize)]
+struct FixerResponse {
+    success: bool,
+    error: Option<FixerError>,
+    rates: Option<HashMap<String, f64>>,
+}
+
+impl FixerResponse {
+    fn into_rate(self, base: &str, target: &str) -> Result<f64, ForexError> {
+        if !self.success {
+            let error = self.error.unwrap_or(FixerError {
+                code: 0,
+                kind: "unknown_error".to_string(),
+            });
+            // 201/202: invalid base/target currency codes.
+            if error.code == 201 || error.code == 202 {
+                return Err(ForexError::InvalidCurrency { symbol: format!("{base}/{target}") });
+            }
+            return Err(ForexError::ProviderError {
+                code: error.code.to_string(),
+                message: format!("Fixer error: {}", error.kind),
+            });
+        }
+        rate_or_pair_not_found(&self.rates.unwrap_or_default(), target)
+    }
+}
+
+impl QuotesProvider for FixerProvider {
+    fn name(&self) -> &'static str {
+        "Fixer"
+    }
+
+    fn latest(&self, base: &str, target: &str) -> Result<f64, ForexError> {
+        let url = format!(
+            "https://data.fixer.io/api/latest?access_key={}&base={base}&symbols={target}",
+            self.api_key
+        );
+        let body: FixerResponse = Client::new()
+            .get(&url)
+            .send()?
+            .json()
+            .map_err(|e| ForexError::Deserialize(e.to_string()))?;
+        body.into_rate(base, target)
+    }
+
+    fn latest_batch(&self, base: &str, targets: &[String]) -> Result<HashMap<String, f64>, ForexError> {
+        let url = format!(
+            "https://data.fixer.io/api/latest?access_key={}&base={base}&symbols={}",
+            self.api_key,
+            targets.join(",")
+        );
+        let body: FixerResponse = Client::new()
+            .get(&url)
+            .send()?
+            .json()
+            .map_err(|e| ForexError::Deserialize(e.to_string()))?;
+        if !body.success {
+            let error = body.error.unwrap_or(FixerError {
+                code: 0,
+                kind: "unknown_error".to_string(),
+            });
+            return Err(ForexError::ProviderError {
+                code: error.code.to_string(),
+                message: format!("Fixer error: {}", error.kind),
+            });
+        }
+        let rates = body.rates.unwrap_or_default();
+        targets
+            .iter()
+            .map(|target| {
+                rates
+                    .get(target)
+                    .copied()
+                    .map(|rate| (target.clone(), rate))
+                    .ok_or_else(|| ForexError::InvalidCurrency { symbol: target.clone() })
+            })
+            .collect()
+    }
+
+    fn historical(&self, base: &str, target: &str, date: NaiveDate) -> Result<f64, ForexError> {
+        let url = format!(
+            "https://data.fixer.io/api/{date}?access_key={}&base={base}&symbols={target}",
+            self.api_key
+        );
+        let body: FixerResponse = Client::new()
+            .get(&url)
+            .send()?
+            .json()
+            .map_err(|e| ForexError::Deserialize(e.to_string()))?;
+        body.into_rate(base, target)
+    }
+}
+
+/// Tries each provider in order, returning the first successful quote (along
+/// with the name of the provider that served it) and falling through to the
+/// next provider on error (e.g. quota exhaustion). If every provider fails,
+/// the last (most recent) error is returned so the UI can show something
+/// actionable instead of a generic failure.
+pub fn fetch_forex_rate(
+    providers: &[Box<dyn QuotesProvider>],
+    base: &str,
+    target: &str,
+) -> Result<(&'static str, f64), ForexError> {
+    let mut last_err = ForexError::PairNotFound;
+    for provider in providers {
+        match provider.latest(base, target) {
+            Ok(rate) => return Ok((provider.name(), rate)),
+            Err(e) => last_err = e,
+        }
+    }
+    Err(last_err)
+}
+
+/// Fetches latest rates for a whole watchlist in as few requests as
+/// possible: entries are grouped by shared base currency so each group costs
+/// one batched request per provider in the fallback chain, rather than one
+/// request per pair. `cache` is consulted first, so pairs refreshed within
+/// `DEFAULT_LATEST_TTL` skip the network entirely, and freshly fetched rates
+/// are written back into it for next time.
+pub fn fetch_rates_batch(
+    providers: &[Box<dyn QuotesProvider>],
+    cache: &mut Cache,
+    pairs: &[(String, String)],
+) -> HashMap<(String, String), Result<f64, ForexError>> {
+    let mut by_base: HashMap<&str, Vec<String>> = HashMap::new();
+    for (base, target) in pairs {
+        by_base.entry(base.as_str()).or_default().push(target.clone());
+    }
+
+    let mut results = HashMap::new();
+    for (base, targets) in by_base {
+        let mut to_fetch = Vec::new();
+        for target in &targets {
+            let cached = providers
+                .iter()
+                .find_map(|p| cache.get(p.name(), base, target, None, cache::DEFAULT_LATEST_TTL));
+            match cached {
+                Some(rate) => {
+                    results.insert((base.to_string(), target.clone()), Ok(rate));
+                }
+                None => to_fetch.push(target.clone()),
+            }
+        }
+        if to_fetch.is_empty() {
+            continue;
+        }
+
+        let mut last_err = None;
+        let mut fetched = None;
+        let mut fetched_by = "";
+        for provider in providers {
+            match provider.latest_batch(base, &to_fetch) {
+                Ok(rates) => {
+                    fetched_by = provider.name();
+                    fetched = Some(rates);
+                    break;
+                }
+                Err(e) => last_err = Some(e),
+            }
+        }
+        for target in &to_fetch {
+            let result = match &fetched {
+                Some(rates) => rates
+                    .get(target)
+                    .copied()
+                    .ok_or_else(|| ForexError::PairNotFound),
+                None => Err(last_err
+                    .as_ref()
+                    .map(|e| ForexError::ProviderError {
+                        code: "batch".to_string(),
+                        message: e.to_string(),
+                    })
+                    .unwrap_or(ForexError::PairNotFound)),
+            };
+            if let Ok(rate) = &result {
+                cache.put(fetched_by, base, target, None, *rate);
+            }
+            results.insert((base.to_string(), target.clone()), result);
+        }
+    }
+    results
+}
+
+/// Same fallback strategy as [`fetch_forex_rate`], for a single historical date.
+pub fn fetch_historical_rate(
+    providers: &[Box<dyn QuotesProvider>],
+    base: &str,
+    target: &str,
+    date: NaiveDate,
+) -> Result<(&'static str, f64), ForexError> {
+    let mut last_err = ForexError::PairNotFound;
+    for provider in providers {
+        match provider.historical(base, target, date) {
+            Ok(rate) => return Ok((provider.name(), rate)),
+            Err(e) => last_err = e,
+        }
+    }
+    Err(last_err)
+}
@@ -0,0 +1,111 @@
+use crate::FetchMessage;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use tungstenite::{connect, Message};
+
+const TICKER_FEED_URL: &str = "wss://ws.kraken.com";
+
+/// Out-of-band messages Kraken sends alongside data, tagged by `event`.
+#[derive(Deserialize)]
+#[serde(tag = "event")]
+enum WsEvent {
+    #[serde(rename = "systemStatus")]
+    SystemStatus {
+        // Only needed to make this variant match `systemStatus` payloads; we
+        // don't currently act on the reported status.
+        #[allow(dead_code)]
+        status: String,
+    },
+    #[serde(rename = "subscriptionStatus")]
+    SubscriptionStatus {
+        status: String,
+        #[serde(default)]
+        #[serde(rename = "errorMessage")]
+        error_message: Option<String>,
+    },
+    #[serde(rename = "heartbeat")]
+    Heartbeat,
+}
+
+/// The `data` object of a `ticker` update, the second element of its
+/// `[channelID, data, channelName, pair]` array.
+#[derive(Deserialize)]
+struct TickerData {
+    /// Best ask: `[price, wholeLotVolume, lotVolume]`.
+    a: Vec<String>,
+    /// Best bid: `[price, wholeLotVolume, lotVolume]`.
+    b: Vec<String>,
+}
+
+/// Kraken's ticker feed sends either a tagged event object or an untagged
+/// `[channelID, data, channelName, pair]` array for data updates.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WsMessage {
+    Event(WsEvent),
+    // The channel ID, channel name and pair are only needed to match the
+    // shape of Kraken's array payload; we already know which pair we
+    // subscribed to, so we don't read them back out.
+    Ticker(#[allow(dead_code)] u64, TickerData, #[allow(dead_code)] String, #[allow(dead_code)] String),
+}
+
+fn mid_price(data: &TickerData) -> Option<f64> {
+    let ask: f64 = data.a.first()?.parse().ok()?;
+    let bid: f64 = data.b.first()?.parse().ok()?;
+    Some((ask + bid) / 2.0)
+}
+
+/// Subscribes to the ticker feed for `pair` and streams mid-price updates to
+/// `tx` as [`FetchMessage::Live`] until `active` is cleared or the socket
+/// errors out. Runs on the calling thread; spawn it on a background thread.
+///
+/// `active` is expected to belong to this call alone (a fresh flag per
+/// stream start, not one reused across restarts), so once the caller clears
+/// it a later restart can't set it again and keep this thread running. Each
+/// update is tagged with `pair`, which lets the caller drop messages this
+/// thread still sends while it is blocked waiting on the next frame.
+pub fn stream_ticker(pair: String, tx: Sender<FetchMessage>, active: Arc<AtomicBool>) {
+    let Ok((mut socket, _)) = connect(TICKER_FEED_URL) else {
+        tx.send(FetchMessage::Error(format!("failed to connect to {TICKER_FEED_URL}"))).ok();
+        return;
+    };
+
+    let subscribe = serde_json::json!({
+        "event": "subscribe",
+        "pair": [pair],
+        "subscription": { "name": "ticker" },
+    });
+    if socket.send(Message::Text(subscribe.to_string())).is_err() {
+        tx.send(FetchMessage::Error("failed to subscribe to ticker feed".to_string())).ok();
+        return;
+    }
+
+    while active.load(Ordering::Relaxed) {
+        let message = match socket.read() {
+            Ok(message) => message,
+            Err(_) => break,
+        };
+        let Message::Text(text) = message else { continue };
+        match serde_json::from_str::<WsMessage>(&text) {
+            Ok(WsMessage::Ticker(_, data, _, _)) => {
+                if let Some(rate) = mid_price(&data) {
+                    tx.send(FetchMessage::Live(pair.clone(), rate)).ok();
+                }
+            }
+            Ok(WsMessage::Event(WsEvent::SubscriptionStatus {
+                status,
+                error_message: Some(message),
+            })) if status == "error" => {
+                tx.send(FetchMessage::Error(format!("ticker subscription failed: {message}"))).ok();
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    // Send a close frame so Kraken tears down the connection promptly
+    // instead of relying on it to notice the TCP socket going away.
+    socket.close(None).ok();
+}